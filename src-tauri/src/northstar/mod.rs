@@ -5,6 +5,36 @@ pub mod install;
 use crate::util::check_ea_app_or_origin_running;
 use crate::{constants::CORE_MODS, get_host_os, GameInstall, InstallType};
 use anyhow::anyhow;
+use pelite::FileMap;
+use serde::{Deserialize, Serialize};
+
+/// Northstar version information, pairing the launcher binary version read from
+/// the PE resources with the version inferred from the installed core mods.
+///
+/// A disagreement between the two usually means an update was only half-applied
+/// (e.g. the DLLs updated but the mods didn't, or vice versa).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NorthstarVersion {
+    /// Version read from the `NorthstarLauncher.exe`/`Northstar.dll` PE resources
+    pub launcher_version: String,
+    /// Version inferred from the core mods' `mod.json` `Version` field
+    pub mods_version: String,
+    /// Whether the binary and the core mods report the same version once
+    /// normalized; `false` is a common half-applied-update symptom
+    pub matches: bool,
+}
+
+/// Normalizes a version string for comparison by trimming a trailing `.0`
+/// fourth (build) component, so a 4-component PE `FileVersion` (`1.29.1.0`) and
+/// a 3-component `mod.json` `Version` (`1.29.1`) compare equal. A `.0` patch
+/// component of a 3-component version is kept (`1.29.0` stays `1.29.0`)
+fn normalize_version(version: &str) -> String {
+    let trimmed = version.trim();
+    match trimmed.strip_suffix(".0") {
+        Some(stripped) if stripped.matches('.').count() >= 2 => stripped.to_string(),
+        _ => trimmed.to_string(),
+    }
+}
 
 /// Check version number of a mod
 pub fn check_mod_version_number(path_to_mod_folder: &str) -> Result<String, anyhow::Error> {
@@ -21,14 +51,62 @@ pub fn check_mod_version_number(path_to_mod_folder: &str) -> Result<String, anyh
     Ok(mod_version_number.to_string())
 }
 
+/// Reads the `FileVersion` string from the PE version resource of a binary
+fn check_binary_version_number(path_to_binary: &str) -> Result<String, anyhow::Error> {
+    let file_map = FileMap::open(path_to_binary)
+        .map_err(|err| anyhow!("Failed to open {path_to_binary}: {err}"))?;
+    let pe = pelite::PeFile::from_bytes(file_map.as_ref())
+        .map_err(|err| anyhow!("Failed to parse {path_to_binary}: {err}"))?;
+
+    let resources = pe
+        .resources()
+        .map_err(|err| anyhow!("No resources in {path_to_binary}: {err}"))?;
+    let version_info = resources
+        .version_info()
+        .map_err(|err| anyhow!("No version info in {path_to_binary}: {err}"))?;
+
+    // Pick the first available language/charset translation
+    let lang = version_info
+        .translation()
+        .first()
+        .copied()
+        .ok_or_else(|| anyhow!("No version translation in {path_to_binary}"))?;
+
+    let version_number = version_info
+        .value(lang, "FileVersion")
+        .ok_or_else(|| anyhow!("No FileVersion in {path_to_binary}"))?;
+
+    log::info!("{}", version_number);
+
+    Ok(version_number)
+}
+
+/// Returns the Northstar launcher binary version read from the PE resources of
+/// `NorthstarLauncher.exe` and `Northstar.dll`
+pub fn get_northstar_launcher_version(game_path: &str) -> Result<String, anyhow::Error> {
+    let launcher_version = check_binary_version_number(&format!("{game_path}/NorthstarLauncher.exe"))?;
+    let dll_version = check_binary_version_number(&format!("{game_path}/Northstar.dll"))?;
+
+    // The two binaries can legitimately carry differing build suffixes, so only
+    // warn about a mismatch rather than failing an otherwise working install.
+    if normalize_version(&launcher_version) != normalize_version(&dll_version) {
+        log::warn!(
+            "NorthstarLauncher.exe ({launcher_version}) and Northstar.dll ({dll_version}) versions disagree"
+        );
+    }
+
+    Ok(launcher_version)
+}
+
 /// Returns the current Northstar version number as a string
 #[tauri::command]
-pub fn get_northstar_version_number(game_path: &str) -> Result<String, String> {
+pub fn get_northstar_version_number(
+    game_path: &str,
+    profile: Option<String>,
+) -> Result<String, String> {
     log::info!("{}", game_path);
 
-    // TODO:
-    // Check if NorthstarLauncher.exe exists and check its version number
-    let profile_folder = "R2Northstar";
+    let profile_folder = profile.as_deref().unwrap_or("R2Northstar");
     let initial_version_number = match check_mod_version_number(&format!(
         "{game_path}/{profile_folder}/mods/{}",
         CORE_MODS[0]
@@ -54,26 +132,53 @@ pub fn get_northstar_version_number(game_path: &str) -> Result<String, String> {
     Ok(initial_version_number)
 }
 
+/// Returns the current Northstar version as both the launcher binary version
+/// and the core mods version, flagging when the two disagree
+#[tauri::command]
+pub fn get_northstar_version(
+    game_path: &str,
+    profile: Option<String>,
+) -> Result<NorthstarVersion, String> {
+    log::info!("{}", game_path);
+
+    let mods_version = get_northstar_version_number(game_path, profile)?;
+    let launcher_version = get_northstar_launcher_version(game_path).map_err(|err| err.to_string())?;
+
+    // Report both versions and whether they agree rather than erroring, so the
+    // frontend can surface a half-applied-update mismatch to the user.
+    let matches = normalize_version(&launcher_version) == normalize_version(&mods_version);
+    if !matches {
+        log::warn!(
+            "Northstar binary version ({launcher_version}) does not match mods version ({mods_version})"
+        );
+    }
+
+    Ok(NorthstarVersion {
+        launcher_version,
+        mods_version,
+        matches,
+    })
+}
+
 /// Launches Northstar
 #[tauri::command]
 pub fn launch_northstar(
     game_install: GameInstall,
     bypass_checks: Option<bool>,
+    profile: Option<String>,
 ) -> Result<String, String> {
     dbg!(game_install.clone());
 
     let host_os = get_host_os();
 
-    // Explicitly fail early certain (currently) unsupported install setups
-    if host_os != "windows"
-        || !(matches!(game_install.install_type, InstallType::STEAM)
-            || matches!(game_install.install_type, InstallType::ORIGIN)
-            || matches!(game_install.install_type, InstallType::UNKNOWN))
+    // Explicitly fail early on install setups we can't launch on any host
+    if !(matches!(game_install.install_type, InstallType::STEAM)
+        || matches!(game_install.install_type, InstallType::ORIGIN)
+        || matches!(game_install.install_type, InstallType::UNKNOWN))
     {
         return Err(format!(
             "Not yet implemented for \"{}\" with Titanfall2 installed via \"{:?}\"",
-            get_host_os(),
-            game_install.install_type
+            host_os, game_install.install_type
         ));
     }
 
@@ -82,13 +187,14 @@ pub fn launch_northstar(
     // Only check guards if bypassing checks is not enabled
     if !bypass_checks {
         // Some safety checks before, should have more in the future
-        if get_northstar_version_number(&game_install.game_path).is_err() {
+        if get_northstar_version_number(&game_install.game_path, profile.clone()).is_err() {
             return Err(anyhow!("Not all checks were met").to_string());
         }
 
-        // Require EA App or Origin to be running to launch Northstar
-        let ea_app_is_running = check_ea_app_or_origin_running();
-        if !ea_app_is_running {
+        // Require EA App or Origin to be running to launch Northstar.
+        // On non-Windows hosts the game runs through Proton, so this check
+        // doesn't apply.
+        if host_os == "windows" && !check_ea_app_or_origin_running() {
             return Err(
                 anyhow!("EA App not running, start EA App before launching Northstar").to_string(),
             );
@@ -102,23 +208,165 @@ pub fn launch_northstar(
         return Err(anyhow!("Couldn't access Titanfall2 directory").to_string());
     }
 
-    // Only Windows with Steam or Origin are supported at the moment
-    if host_os == "windows"
-        && (matches!(game_install.install_type, InstallType::STEAM)
-            || matches!(game_install.install_type, InstallType::ORIGIN)
-            || matches!(game_install.install_type, InstallType::UNKNOWN))
-    {
-        let ns_exe_path = format!("{}/NorthstarLauncher.exe", game_install.game_path);
-        let _output = std::process::Command::new("C:\\Windows\\System32\\cmd.exe")
-            .args(["/C", "start", "", &ns_exe_path])
+    // Dispatch to the correct launch path for the current host OS
+    match host_os.as_str() {
+        "windows" => launch_northstar_windows(&game_install, profile.as_deref()),
+        "linux" => launch_northstar_linux(&game_install, profile.as_deref()),
+        _ => Err(format!(
+            "Not yet implemented for {:?} on {}",
+            game_install.install_type, host_os
+        )),
+    }
+}
+
+/// Launches Northstar on Windows by starting `NorthstarLauncher.exe` directly
+fn launch_northstar_windows(
+    game_install: &GameInstall,
+    profile: Option<&str>,
+) -> Result<String, String> {
+    let ns_exe_path = format!("{}/NorthstarLauncher.exe", game_install.game_path);
+    let mut args = vec![
+        "/C".to_string(),
+        "start".to_string(),
+        "".to_string(),
+        ns_exe_path,
+    ];
+    if let Some(profile) = profile {
+        args.push(format!("-profile={profile}"));
+    }
+    let _output = std::process::Command::new("C:\\Windows\\System32\\cmd.exe")
+        .args(&args)
+        .spawn()
+        .expect("failed to execute process");
+    Ok("Launched game".to_string())
+}
+
+/// Launches Northstar on Linux through the Steam Play/Proton prefix
+///
+/// Titanfall2 has no native Linux build, so the launcher binary is run inside
+/// the same Proton prefix Steam uses for the game. When the `NorthstarProton`
+/// wrapper is installed we hand off to Steam via `steam -applaunch`, otherwise
+/// we invoke a Proton runtime found by `find_proton_runtime` directly with the
+/// prefix environment (`STEAM_COMPAT_DATA_PATH`/`WINEPREFIX`) set up by hand.
+/// The Proton version Steam has configured for the game is not read.
+fn launch_northstar_linux(
+    game_install: &GameInstall,
+    profile: Option<&str>,
+) -> Result<String, String> {
+    // Only Steam installs have a Proton prefix we can reuse on Linux
+    if !matches!(game_install.install_type, InstallType::STEAM) {
+        return Err(format!(
+            "Not yet implemented for {:?} on linux",
+            game_install.install_type
+        ));
+    }
+
+    let ns_exe_path = format!("{}/NorthstarLauncher.exe", game_install.game_path);
+
+    // `.../steamapps/common/Titanfall2` -> `.../steamapps`
+    let steamapps_dir = std::path::Path::new(&game_install.game_path)
+        .ancestors()
+        .nth(2)
+        .ok_or_else(|| anyhow!("Couldn't locate Steam library for Titanfall2").to_string())?;
+
+    // The Proton prefix for Titanfall2 (appid 1237970) lives next to the game
+    let compat_data_path = steamapps_dir.join("compatdata").join("1237970");
+    if !compat_data_path.exists() {
+        return Err(anyhow!(
+            "Couldn't find Proton prefix for Titanfall2; run the game through Steam at least once"
+        )
+        .to_string());
+    }
+    let wine_prefix = compat_data_path.join("pfx");
+
+    // The Steam root holds the Linux Runtime and the user's compatibility tools
+    let steam_root = steamapps_dir
+        .parent()
+        .ok_or_else(|| anyhow!("Couldn't locate Steam root for Titanfall2").to_string())?;
+
+    // Prefer the NorthstarProton wrapper when the user has it installed, as it
+    // carries the patches Northstar needs; Steam knows how to launch it.
+    let northstar_proton = steam_root
+        .join("compatibilitytools.d")
+        .join("NorthstarProton");
+    if northstar_proton.exists() {
+        let mut args = vec!["-applaunch".to_string(), "1237970".to_string()];
+        if let Some(profile) = profile {
+            args.push(format!("-profile={profile}"));
+        }
+        let _output = std::process::Command::new("steam")
+            .args(&args)
             .spawn()
-            .expect("failed to execute process");
+            .map_err(|err| anyhow!("Failed to launch Steam: {err}").to_string())?;
         return Ok("Launched game".to_string());
     }
 
-    Err(format!(
-        "Not yet implemented for {:?} on {}",
-        game_install.install_type,
-        get_host_os()
-    ))
+    // Fall back to running the launcher through a real Proton runtime. The
+    // prefix dir itself holds no `proton` executable, so resolve the actual
+    // install from the Steam libraries.
+    let proton_bin = find_proton_runtime(steamapps_dir).ok_or_else(|| {
+        anyhow!(
+            "Couldn't find a Proton runtime; install Proton through Steam or the NorthstarProton wrapper"
+        )
+        .to_string()
+    })?;
+    let mut args = vec!["run".to_string(), ns_exe_path];
+    if let Some(profile) = profile {
+        args.push(format!("-profile={profile}"));
+    }
+    let _output = std::process::Command::new(proton_bin)
+        .args(&args)
+        .env("STEAM_COMPAT_DATA_PATH", &compat_data_path)
+        .env("WINEPREFIX", &wine_prefix)
+        // `proton run` needs the Steam root to locate the Steam Linux Runtime
+        .env("STEAM_COMPAT_CLIENT_INSTALL_PATH", steam_root)
+        .spawn()
+        .map_err(|err| anyhow!("Failed to launch Northstar through Proton: {err}").to_string())?;
+
+    Ok("Launched game".to_string())
+}
+
+/// Locates a `proton` runtime executable from the Steam libraries, searching
+/// both official installs under `steamapps/common/Proton*` and user-installed
+/// compatibility tools under `compatibilitytools.d/`
+fn find_proton_runtime(steamapps_dir: &std::path::Path) -> Option<std::path::PathBuf> {
+    // Official Proton builds: `steamapps/common/Proton <version>/proton`.
+    // `read_dir` order is unspecified, so choose deterministically: prefer
+    // "Proton - Experimental", otherwise the highest-named version.
+    let common_dir = steamapps_dir.join("common");
+    if let Ok(entries) = common_dir.read_dir() {
+        let mut candidates: Vec<std::path::PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.starts_with("Proton"))
+                    .unwrap_or(false)
+            })
+            .filter(|p| p.join("proton").exists())
+            .collect();
+        candidates.sort();
+        let chosen = candidates
+            .iter()
+            .find(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|n| n.contains("Experimental"))
+                    .unwrap_or(false)
+            })
+            .or_else(|| candidates.last());
+        if let Some(chosen) = chosen {
+            return Some(chosen.join("proton"));
+        }
+    }
+
+    // User-installed compatibility tools: `<steam>/compatibilitytools.d/*/proton`
+    let tools_dir = steamapps_dir.parent()?.join("compatibilitytools.d");
+    tools_dir.read_dir().ok().and_then(|entries| {
+        entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path().join("proton"))
+            .find(|p| p.exists())
+    })
 }
@@ -37,10 +37,11 @@ pub async fn install_plugin(
     zip_file: &File,
     thunderstore_mod_string: &str,
     can_install_plugins: bool,
+    profile: Option<&str>,
 ) -> Result<(), ThermiteError> {
     let plugins_directory = PathBuf::new()
         .join(&game_install.game_path)
-        .join("R2Northstar")
+        .join(profile.unwrap_or("R2Northstar"))
         .join("plugins");
     let temp_dir = TempDir::create(plugins_directory.join("___flightcore-temp-plugin-dir"))?;
     let manifest_path = temp_dir.join("manifest.json");
@@ -116,12 +117,49 @@ pub async fn install_plugin(
             ))?
         }
     } else {
-        Err(ThermiteError::MissingFile(Box::new(
-            temp_dir.join("plugins/anyplugins.dll"),
-        )))?;
+        // A thunderstore zip with no plugin DLLs simply isn't a plugin package;
+        // say so instead of claiming some specific file is missing.
+        Err(ThermiteError::MiscError(
+            "the provided package contains no plugins".to_string(),
+        ))?
     }
 
-    // nuke previous version if it exists
+    // Stage the finished plugin fully before touching the installed copy so a
+    // failed copy can never leave the user with no working plugin at all.
+    let staging_dir = temp_dir.join("___flightcore-staged-plugin");
+    if staging_dir.exists() {
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    fs::create_dir(&staging_dir)?;
+
+    let staged_manifest = staging_dir.join(manifest_path.file_name().unwrap_or_default());
+    fs::copy(&manifest_path, &staged_manifest)?;
+    for file in &plugins {
+        fs::copy(file.path(), staging_dir.join(file.file_name()))?;
+    }
+
+    // Verify everything we expect made it into the staging dir
+    if !staged_manifest.exists() {
+        Err(ThermiteError::MissingFile(Box::new(staged_manifest)))?;
+    }
+    for file in &plugins {
+        let staged = staging_dir.join(file.file_name());
+        if !staged.exists() {
+            Err(ThermiteError::MissingFile(Box::new(staged)))?;
+        }
+    }
+
+    // Restores every already-moved backup to its original location; used to
+    // undo a partial backup/swap so the user never loses the version they had.
+    let restore = |backups: &[(PathBuf, PathBuf)]| {
+        for (orig, backup) in backups {
+            let _ = fs::rename(backup, orig);
+        }
+    };
+
+    // Move any previously installed versions of this package aside (rather than
+    // deleting them outright) so they can be restored if a later step fails.
+    let mut backups: Vec<(PathBuf, PathBuf)> = Vec::new();
     for (_, path) in plugins_directory
         .read_dir()
         .map_err(|_| ThermiteError::MissingFile(Box::new(temp_dir.join("plugins"))))?
@@ -131,23 +169,30 @@ pub async fn install_plugin(
         .filter_map(|path| Some((path.clone().file_name()?.to_str()?.to_owned(), path)))
         .filter_map(|(name, path)| Some((name.parse::<ParsedThunderstoreModString>().ok()?, path)))
         .filter(|(p, _)| p.mod_name == package_name)
-        .inspect(|(_, path)| println!("removing {}", path.display()))
     {
-        fs::remove_dir_all(path)?
+        let backup = temp_dir.join(format!(
+            "___flightcore-backup-{}",
+            path.file_name().and_then(|n| n.to_str()).unwrap_or_default()
+        ));
+        if let Err(err) = fs::rename(&path, &backup) {
+            // Put back everything already moved before bailing out
+            restore(&backups);
+            return Err(err.into());
+        }
+        backups.push((path, backup));
     }
 
-    // create the plugin subdir
-    if !this_plugin_dir.exists() {
-        fs::create_dir(&this_plugin_dir)?;
+    // Swap the staged plugin into place atomically; on failure roll everything
+    // back so the user keeps the version they had.
+    if let Err(err) = fs::rename(&staging_dir, &this_plugin_dir) {
+        let _ = fs::remove_dir_all(&this_plugin_dir);
+        restore(&backups);
+        return Err(err.into());
     }
 
-    fs::copy(
-        &manifest_path,
-        this_plugin_dir.join(manifest_path.file_name().unwrap_or_default()),
-    )?;
-
-    for file in plugins {
-        fs::copy(file.path(), this_plugin_dir.join(file.file_name()))?;
+    // The swap succeeded, so the previous versions are now stale; drop them.
+    for (_, backup) in backups {
+        let _ = fs::remove_dir_all(backup);
     }
 
     Ok(())